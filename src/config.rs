@@ -1,4 +1,6 @@
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
 
 use hyper::http::uri::PathAndQuery;
 
@@ -6,9 +8,10 @@ use hyper::http::uri::PathAndQuery;
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(default))]
 pub struct Config {
-    /// Listen address of metrics. Used by the client to gather prometheus metrics.
-    /// Default: `127.0.0.1:10000`
-    pub listen_address: SocketAddr,
+    /// Listen target of metrics. Used by the client to gather prometheus metrics.
+    /// Either a TCP `SocketAddr` or a Unix domain socket (`unix:/path/to/socket`).
+    /// Default: `0.0.0.0:10000`
+    pub listen_address: ListenAddress,
 
     /// Path to the metrics if specified. Any path will work otherwise
     /// Default: None
@@ -20,14 +23,221 @@ pub struct Config {
 
     /// Metrics update interval in seconds. Default: 10
     pub collection_interval_sec: u64,
+
+    /// Log each completed metrics request (remote peer, response status, body
+    /// size and collection time). Default: false
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub log_requests: bool,
+
+    /// Optional TLS configuration. When specified the endpoint is served over
+    /// HTTPS and plaintext connections are rejected.
+    /// Default: None
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tls: Option<TlsConfig>,
+
+    /// Optional authentication for the endpoint. When specified requests must
+    /// carry a matching `Authorization` header.
+    /// Default: None
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub auth: Option<AuthConfig>,
+
+    /// Optional push-gateway target. When specified the exporter does not host
+    /// an HTTP endpoint, but periodically pushes the current buffer to the
+    /// configured collector instead.
+    /// Default: None
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub push: Option<PushConfig>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            listen_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 10000),
+            listen_address: ListenAddress::Tcp(SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
+                10000,
+            )),
             metrics_path: None,
             collection_interval_sec: 10,
+            log_requests: false,
+            tls: None,
+            auth: None,
+            push: None,
+        }
+    }
+}
+
+/// Push-gateway configuration.
+///
+/// In push mode the current buffer is sent to `endpoint` every
+/// `collection_interval_sec` seconds. The `job` and optional `instance`
+/// grouping labels are appended to the request path following the
+/// pushgateway `/metrics/job/<job>/instance/<instance>` convention.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PushConfig {
+    /// Base URL of the pushgateway, e.g. `http://127.0.0.1:9091`.
+    pub endpoint: String,
+
+    /// `job` grouping label.
+    pub job: String,
+
+    /// Optional `instance` grouping label.
+    pub instance: Option<String>,
+
+    /// HTTP method used to push. Default: `PUT`.
+    pub method: PushMethod,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            job: String::new(),
+            instance: None,
+            method: PushMethod::Put,
         }
     }
 }
+
+/// HTTP method used when pushing metrics to a pushgateway.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "UPPERCASE"))]
+pub enum PushMethod {
+    /// Replace the group's metrics (pushgateway `PUT`).
+    Put,
+    /// Merge into the group's metrics (pushgateway `POST`).
+    Post,
+}
+
+/// Authentication configuration for the metrics endpoint.
+///
+/// A static bearer token and/or HTTP basic credentials may be configured;
+/// a request is accepted if its `Authorization` header matches any of them.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AuthConfig {
+    /// Static bearer token expected in `Authorization: Bearer <token>`.
+    pub bearer_token: Option<String>,
+
+    /// HTTP basic auth username. Must be set together with `basic_password`;
+    /// setting only one of the two fails the reload.
+    pub basic_username: Option<String>,
+
+    /// HTTP basic auth password. Must be set together with `basic_username`;
+    /// setting only one of the two fails the reload.
+    pub basic_password: Option<String>,
+}
+
+/// Target the metrics endpoint listens on.
+///
+/// Parsed from a string: a `unix:`-prefixed value selects a Unix domain socket
+/// at the given path, anything else is parsed as a TCP [`SocketAddr`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub enum ListenAddress {
+    /// TCP socket address.
+    Tcp(SocketAddr),
+    /// Unix domain socket path.
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddress {
+    type Err = std::net::AddrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("unix:") {
+            Some(path) => Ok(ListenAddress::Unix(PathBuf::from(path))),
+            None => Ok(ListenAddress::Tcp(s.parse()?)),
+        }
+    }
+}
+
+impl std::fmt::Display for ListenAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenAddress::Tcp(addr) => std::fmt::Display::fmt(addr, f),
+            ListenAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl From<SocketAddr> for ListenAddress {
+    fn from(addr: SocketAddr) -> Self {
+        ListenAddress::Tcp(addr)
+    }
+}
+
+impl TryFrom<String> for ListenAddress {
+    type Error = std::net::AddrParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ListenAddress> for String {
+    fn from(value: ListenAddress) -> Self {
+        value.to_string()
+    }
+}
+
+/// TLS configuration for the metrics endpoint.
+///
+/// The certificate chain and private key can either be read from files
+/// (`cert_path`/`key_path`) or passed inline as PEM bytes (`cert_pem`/`key_pem`).
+/// Inline values take precedence over paths. Both a certificate and a key must
+/// be provided, otherwise [`MetricsExporter::reload`] fails with a clear error.
+///
+/// [`MetricsExporter::reload`]: crate::MetricsExporter::reload
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TlsConfig {
+    /// Path to the PEM-encoded certificate chain.
+    pub cert_path: Option<PathBuf>,
+
+    /// Path to the PEM-encoded private key (PKCS#8 or RSA).
+    pub key_path: Option<PathBuf>,
+
+    /// Inline PEM-encoded certificate chain. Takes precedence over `cert_path`.
+    pub cert_pem: Option<String>,
+
+    /// Inline PEM-encoded private key. Takes precedence over `key_path`.
+    pub key_pem: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listen_address_tcp_round_trip() {
+        let parsed: ListenAddress = "127.0.0.1:8080".parse().unwrap();
+        assert_eq!(
+            parsed,
+            ListenAddress::Tcp("127.0.0.1:8080".parse().unwrap())
+        );
+        assert_eq!(parsed.to_string(), "127.0.0.1:8080");
+        assert_eq!(parsed.to_string().parse::<ListenAddress>().unwrap(), parsed);
+    }
+
+    #[test]
+    fn listen_address_unix_round_trip() {
+        let parsed: ListenAddress = "unix:/run/metrics.sock".parse().unwrap();
+        assert_eq!(
+            parsed,
+            ListenAddress::Unix(PathBuf::from("/run/metrics.sock"))
+        );
+        assert_eq!(parsed.to_string(), "unix:/run/metrics.sock");
+        assert_eq!(parsed.to_string().parse::<ListenAddress>().unwrap(), parsed);
+    }
+
+    #[test]
+    fn listen_address_rejects_invalid_tcp() {
+        assert!("not-an-address".parse::<ListenAddress>().is_err());
+    }
+}
@@ -1,11 +1,18 @@
 use std::convert::Infallible;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use futures::future::Either;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use hyper::server::accept::Accept;
+use hyper::server::conn::{AddrIncoming, AddrStream};
 use tokio::sync::{Mutex, Notify, RwLock, RwLockWriteGuard};
+use tokio_rustls::TlsAcceptor;
 
 use crate::config::*;
 use crate::utils::*;
@@ -68,16 +75,99 @@ impl MetricsExporter {
             }
         };
 
-        // Create http service
-        let server = hyper::Server::try_bind(&config.listen_address)
-            .context("Failed to bind metrics exporter server port")?;
+        // In push mode we don't host an endpoint: spawn a loop that pushes the
+        // current buffer to the configured collector every interval instead.
+        if let Some(push) = config.push.clone() {
+            validate_push_config(&push)
+                .context("Invalid metrics exporter push configuration")?;
+
+            let buffers = self.handle.buffers.clone();
+            let handle = self.handle.clone();
+
+            let completion_signal = self.completion_signal.clone();
+            let (stopped_trigger, stopped_signal) = trigger();
+            let (local_completion_trigger, local_completion_signal) = trigger();
+
+            log::info!("Metrics push loop started");
+
+            tokio::spawn(async move {
+                let client = hyper::Client::new();
+                let shutdown = futures::future::select(completion_signal, local_completion_signal);
+                futures::pin_mut!(shutdown);
+
+                loop {
+                    let EncodedMetrics { body, .. } = buffers.get_metrics(false).await;
+
+                    // Race the push against shutdown so an unresponsive
+                    // collector can't block `reload` on `stopped_signal`.
+                    tokio::select! {
+                        _ = &mut shutdown => break,
+                        _ = push_metrics(&client, &push, body) => {}
+                    }
+
+                    tokio::select! {
+                        _ = &mut shutdown => break,
+                        _ = handle.wait() => continue,
+                    }
+                }
+
+                log::info!("Metrics push loop stopped");
+                stopped_trigger.trigger();
+            });
+
+            *running_endpoint = Some(RunningEndpoint {
+                completion_trigger: local_completion_trigger,
+                stopped_signal,
+            });
+
+            self.handle
+                .interval_sec
+                .store(config.collection_interval_sec, Ordering::Release);
+            self.handle.new_config_notify.notify_waiters();
+
+            return Ok(());
+        }
+
+        // Bind the listener and, when configured, build the TLS acceptor. Both
+        // are rebuilt on every reload so rotated certificates are picked up.
+        let raw = match &config.listen_address {
+            ListenAddress::Tcp(addr) => {
+                let incoming = AddrIncoming::bind(addr)
+                    .context("Failed to bind metrics exporter server port")?;
+                RawIncoming::Tcp(incoming)
+            }
+            ListenAddress::Unix(path) => RawIncoming::Unix(
+                UnixIncoming::bind(path.clone())
+                    .context("Failed to bind metrics exporter unix socket")?,
+            ),
+        };
+
+        let incoming = match &config.tls {
+            Some(tls) => {
+                let acceptor = build_tls_acceptor(tls)
+                    .context("Failed to build metrics exporter TLS acceptor")?;
+                MetricsIncoming::Tls(TlsIncoming::new(raw, acceptor))
+            }
+            None => MetricsIncoming::Plain(raw),
+        };
 
         let path = config.metrics_path.clone();
         let buffers = self.handle.buffers.clone();
+        let log_requests = config.log_requests;
+        let auth = match &config.auth {
+            Some(auth) => Some(Arc::new(
+                PreparedAuth::from_config(auth)
+                    .context("Invalid metrics exporter auth configuration")?,
+            )),
+            None => None,
+        };
 
-        let make_service = hyper::service::make_service_fn(move |_| {
+        let make_service = hyper::service::make_service_fn(move |conn: &MetricsStream| {
             let path = path.clone();
             let buffers = buffers.clone();
+            let auth = auth.clone();
+            // Capture the remote peer once per connection for request logging.
+            let peer = log_requests.then(|| conn.remote_peer());
 
             async move {
                 Ok::<_, Infallible>(hyper::service::service_fn(move |req| {
@@ -90,14 +180,48 @@ impl MetricsExporter {
                         ));
                     }
 
+                    // Reject unauthenticated requests when auth is configured.
+                    if let Some(auth) = &auth {
+                        if !auth.is_authorized(req.headers()) {
+                            return Either::Left(futures::future::ready(
+                                hyper::Response::builder()
+                                    .status(hyper::StatusCode::UNAUTHORIZED)
+                                    .header(hyper::header::WWW_AUTHENTICATE, auth.challenge.as_str())
+                                    .body(hyper::Body::empty()),
+                            ));
+                        }
+                    }
+
                     let buffers = buffers.clone();
+                    let peer = peer.clone();
+                    let accept_gzip = accepts_gzip(req.headers());
 
                     // Prepare metrics response
                     Either::Right(async move {
-                        let data = buffers.get_metrics().await;
-                        hyper::Response::builder()
-                            .header("Content-Type", "text/plain; charset=UTF-8")
-                            .body(hyper::Body::from(data))
+                        // Measure only the time spent collecting the buffer.
+                        let started_at = std::time::Instant::now();
+                        let EncodedMetrics { body, gzip } = buffers.get_metrics(accept_gzip).await;
+                        let elapsed = started_at.elapsed();
+
+                        let body_size = body.len();
+                        let mut builder = hyper::Response::builder()
+                            .header("Content-Type", "text/plain; charset=UTF-8");
+                        if gzip {
+                            builder = builder.header(hyper::header::CONTENT_ENCODING, "gzip");
+                        }
+                        let response = builder.body(hyper::Body::from(body));
+
+                        if let Some(peer) = peer {
+                            let status = match &response {
+                                Ok(response) => response.status(),
+                                Err(_) => hyper::StatusCode::INTERNAL_SERVER_ERROR,
+                            };
+                            log::info!(
+                                "Metrics request from {peer}: status={status}, body={body_size} bytes, collected in {elapsed:?}"
+                            );
+                        }
+
+                        response
                     })
                 }))
             }
@@ -112,7 +236,7 @@ impl MetricsExporter {
 
         // Spawn server
         tokio::spawn(async move {
-            let server = server
+            let server = hyper::Server::builder(incoming)
                 .serve(make_service)
                 .with_graceful_shutdown(async move {
                     futures::future::select(completion_signal, local_completion_signal).await;
@@ -229,9 +353,19 @@ struct RunningEndpoint {
     stopped_signal: TriggerReceiver,
 }
 
+/// A single metrics buffer entry with a lazily-computed gzip cache.
+///
+/// The cache is cleared in [`Buffers::acquire_buffer`] whenever the entry is
+/// reused, so a stale compression can never outlive the plaintext it encodes.
+#[derive(Default)]
+struct Buffer {
+    data: String,
+    gzip: Option<Arc<Vec<u8>>>,
+}
+
 #[derive(Default)]
 struct Buffers {
-    data: [RwLock<String>; BUFFER_COUNT],
+    data: [RwLock<Buffer>; BUFFER_COUNT],
     current_buffer: AtomicUsize,
 }
 
@@ -242,7 +376,9 @@ impl Buffers {
     {
         let next_buffer = (self.current_buffer.load(Ordering::Acquire) + 1) % BUFFER_COUNT;
         let mut buffer_guard = self.data[next_buffer].write().await;
-        buffer_guard.clear();
+        buffer_guard.data.clear();
+        // Invalidate the cached compression of the previous contents.
+        buffer_guard.gzip = None;
         MetricsBuffer {
             current_buffer: &self.current_buffer,
             next_buffer,
@@ -250,18 +386,95 @@ impl Buffers {
         }
     }
 
-    async fn get_metrics(&self) -> String {
-        self.data[self.current_buffer.load(Ordering::Acquire)]
-            .read()
-            .await
-            .clone()
+    /// Returns the current buffer, gzip-compressed when `accept_gzip` is set.
+    ///
+    /// The compressed representation is cached on the entry so repeated scrapes
+    /// of an unchanged buffer don't recompress it.
+    async fn get_metrics(&self, accept_gzip: bool) -> EncodedMetrics {
+        let index = self.current_buffer.load(Ordering::Acquire);
+
+        if !accept_gzip {
+            let guard = self.data[index].read().await;
+            return EncodedMetrics {
+                body: guard.data.clone().into_bytes(),
+                gzip: false,
+            };
+        }
+
+        // Fast path: serve the cached compression.
+        {
+            let guard = self.data[index].read().await;
+            if let Some(gzip) = &guard.gzip {
+                return EncodedMetrics {
+                    body: gzip.as_ref().clone(),
+                    gzip: true,
+                };
+            }
+        }
+
+        // Slow path: compress once and cache the result.
+        let mut guard = self.data[index].write().await;
+        if guard.gzip.is_none() {
+            let compressed = gzip_compress(guard.data.as_bytes());
+            guard.gzip = Some(Arc::new(compressed));
+        }
+        let gzip = guard.gzip.as_ref().expect("gzip cache just populated");
+        EncodedMetrics {
+            body: gzip.as_ref().clone(),
+            gzip: true,
+        }
+    }
+}
+
+/// An encoded metrics response: the body bytes and whether they are gzipped.
+struct EncodedMetrics {
+    body: Vec<u8>,
+    gzip: bool,
+}
+
+/// Returns whether the request advertises `gzip` in its `Accept-Encoding`
+/// header. An explicit `gzip;q=0` disables it, matching the HTTP semantics.
+fn accepts_gzip(headers: &hyper::HeaderMap) -> bool {
+    let value = match headers
+        .get(hyper::header::ACCEPT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(value) => value,
+        None => return false,
+    };
+
+    value.split(',').any(|entry| {
+        let mut parts = entry.split(';');
+        let coding = parts.next().unwrap_or("").trim();
+        if !coding.eq_ignore_ascii_case("gzip") {
+            return false;
+        }
+        // Honor a `q=0` weight, which explicitly refuses the encoding.
+        !parts.any(|param| {
+            let param = param.trim();
+            matches!(param.strip_prefix("q="), Some(q) if q.trim().parse::<f32>() == Ok(0.0))
+        })
+    })
+}
+
+/// Compresses metrics bytes with gzip at the default compression level.
+fn gzip_compress(data: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(
+        Vec::with_capacity(data.len() / 2),
+        flate2::Compression::default(),
+    );
+    if encoder.write_all(data).is_err() {
+        return Vec::new();
     }
+    encoder.finish().unwrap_or_default()
 }
 
 pub struct MetricsBuffer<'a> {
     current_buffer: &'a AtomicUsize,
     next_buffer: usize,
-    buffer_guard: RwLockWriteGuard<'a, String>,
+    buffer_guard: RwLockWriteGuard<'a, Buffer>,
 }
 
 impl<'a> MetricsBuffer<'a> {
@@ -269,7 +482,7 @@ impl<'a> MetricsBuffer<'a> {
     where
         T: std::fmt::Display,
     {
-        self.buffer_guard.push_str(&metrics.to_string());
+        self.buffer_guard.data.push_str(&metrics.to_string());
         self
     }
 }
@@ -282,3 +495,694 @@ impl<'a> Drop for MetricsBuffer<'a> {
 }
 
 const BUFFER_COUNT: usize = 2;
+
+/// Pushes a metrics buffer to the configured pushgateway.
+///
+/// Errors and non-success responses are logged rather than propagated so a
+/// transient collector outage can't tear down the push loop.
+async fn push_metrics(
+    client: &hyper::Client<hyper::client::HttpConnector>,
+    config: &PushConfig,
+    data: Vec<u8>,
+) {
+    let method = match config.method {
+        PushMethod::Put => hyper::Method::PUT,
+        PushMethod::Post => hyper::Method::POST,
+    };
+
+    let body_size = data.len();
+    let uri = push_uri(config);
+
+    let request = match hyper::Request::builder()
+        .method(method)
+        .uri(&uri)
+        .header(hyper::header::CONTENT_TYPE, "text/plain; charset=UTF-8")
+        .body(hyper::Body::from(data))
+    {
+        Ok(request) => request,
+        Err(e) => {
+            log::error!("Failed to build metrics push request to {uri}: {e:?}");
+            return;
+        }
+    };
+
+    // Bound the request so a collector that accepts the connection but never
+    // responds can't stall the push loop indefinitely.
+    match tokio::time::timeout(PUSH_REQUEST_TIMEOUT, client.request(request)).await {
+        Ok(Ok(response)) if response.status().is_success() => {
+            log::debug!("Pushed {body_size} bytes of metrics to {uri}");
+        }
+        Ok(Ok(response)) => {
+            log::warn!("Pushgateway {uri} returned {}", response.status());
+        }
+        Ok(Err(e)) => {
+            log::error!("Failed to push metrics to {uri}: {e:?}");
+        }
+        Err(_) => {
+            log::error!("Timed out pushing metrics to {uri}");
+        }
+    }
+}
+
+/// Upper bound on a single push request before it is abandoned.
+const PUSH_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Validates a push configuration, mirroring the fail-loud handling of bad
+/// bind/TLS/auth: an empty target or job, or an unparseable endpoint, fails the
+/// reload instead of spinning a loop that pushes to a broken URL forever.
+fn validate_push_config(config: &PushConfig) -> Result<()> {
+    anyhow::ensure!(!config.endpoint.is_empty(), "push `endpoint` must not be empty");
+    anyhow::ensure!(!config.job.is_empty(), "push `job` label must not be empty");
+    push_uri(config)
+        .parse::<hyper::Uri>()
+        .context("push `endpoint` is not a valid URI")?;
+    Ok(())
+}
+
+/// Builds the pushgateway request path from the job/instance grouping labels.
+///
+/// Label values are percent-encoded so reserved characters (`/`, spaces, ...)
+/// can't produce a malformed or misrouted URL.
+fn push_uri(config: &PushConfig) -> String {
+    let base = config.endpoint.trim_end_matches('/');
+    let mut uri = format!("{base}/metrics/job/{}", encode_label_segment(&config.job));
+    if let Some(instance) = &config.instance {
+        uri.push_str("/instance/");
+        uri.push_str(&encode_label_segment(instance));
+    }
+    uri
+}
+
+/// Percent-encodes everything outside the unreserved set (RFC 3986) so a
+/// grouping-label value is safe to splice into the request path.
+fn encode_label_segment(value: &str) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::with_capacity(value.len());
+    for &byte in value.as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char);
+            }
+            other => {
+                let _ = write!(out, "%{other:02X}");
+            }
+        }
+    }
+    out
+}
+
+/// Prepared authentication checker derived from [`AuthConfig`].
+///
+/// Holds the set of accepted `Authorization` header values (one per configured
+/// scheme) and the `WWW-Authenticate` challenge to return on rejection.
+struct PreparedAuth {
+    allowed: Vec<String>,
+    challenge: String,
+}
+
+impl PreparedAuth {
+    /// Builds the checker from the configured credentials.
+    ///
+    /// Returns an error when `auth` is present but unusable — a partially filled
+    /// basic block or no credentials at all — so a misconfiguration fails the
+    /// reload loudly instead of silently serving the endpoint unprotected.
+    fn from_config(config: &AuthConfig) -> Result<Self> {
+        let mut allowed = Vec::new();
+
+        if let Some(token) = &config.bearer_token {
+            allowed.push(format!("Bearer {token}"));
+        }
+
+        match (&config.basic_username, &config.basic_password) {
+            (Some(user), Some(pass)) => {
+                use base64::Engine;
+                let encoded =
+                    base64::engine::general_purpose::STANDARD.encode(format!("{user}:{pass}"));
+                allowed.push(format!("Basic {encoded}"));
+            }
+            (None, None) => {}
+            _ => anyhow::bail!("basic auth requires both `basic_username` and `basic_password`"),
+        }
+
+        anyhow::ensure!(
+            !allowed.is_empty(),
+            "auth is configured but no bearer token or basic credentials were provided"
+        );
+
+        // Prefer a Basic challenge when basic credentials are configured.
+        let challenge = if config.basic_username.is_some() {
+            "Basic realm=\"metrics\"".to_owned()
+        } else {
+            "Bearer".to_owned()
+        };
+
+        Ok(Self { allowed, challenge })
+    }
+
+    /// Checks the request `Authorization` header against every accepted
+    /// credential in constant time.
+    fn is_authorized(&self, headers: &hyper::HeaderMap) -> bool {
+        let provided = match headers
+            .get(hyper::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+        {
+            Some(value) => value,
+            None => return false,
+        };
+
+        let mut authorized = false;
+        for expected in &self.allowed {
+            authorized |= constant_time_eq(expected.as_bytes(), provided.as_bytes());
+        }
+        authorized
+    }
+}
+
+/// Constant-time byte comparison, avoiding the early-exit timing leak of `==`.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+/// Builds a rustls-based TLS acceptor from the exporter configuration.
+fn build_tls_acceptor(config: &TlsConfig) -> Result<TlsAcceptor> {
+    use tokio_rustls::rustls;
+
+    let cert_pem = load_pem(config.cert_pem.as_deref(), config.cert_path.as_deref())
+        .context("Failed to load TLS certificate chain")?;
+    let key_pem = load_pem(config.key_pem.as_deref(), config.key_path.as_deref())
+        .context("Failed to load TLS private key")?;
+
+    let certs = rustls_pemfile::certs(&mut cert_pem.as_slice())
+        .context("Failed to parse TLS certificate chain")?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect::<Vec<_>>();
+    anyhow::ensure!(!certs.is_empty(), "TLS certificate chain is empty");
+
+    let key = load_private_key(&key_pem)?;
+
+    let mut server_config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("Failed to build TLS server config")?;
+    // Advertise only the protocols the crate was built to speak, otherwise a
+    // client may negotiate e.g. `h2` against an `http1`-only server.
+    let mut alpn = Vec::new();
+    #[cfg(feature = "http2")]
+    alpn.push(b"h2".to_vec());
+    #[cfg(feature = "http1")]
+    alpn.push(b"http/1.1".to_vec());
+    server_config.alpn_protocols = alpn;
+
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// Resolves PEM bytes from an inline value or a file path. Inline values take
+/// precedence.
+fn load_pem(inline: Option<&str>, path: Option<&std::path::Path>) -> Result<Vec<u8>> {
+    match (inline, path) {
+        (Some(pem), _) => Ok(pem.as_bytes().to_vec()),
+        (None, Some(path)) => {
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))
+        }
+        (None, None) => anyhow::bail!("neither inline PEM nor a path was provided"),
+    }
+}
+
+/// Extracts the first PKCS#8, RSA or EC private key from PEM data.
+fn load_private_key(pem: &[u8]) -> Result<tokio_rustls::rustls::PrivateKey> {
+    let mut reader = pem;
+    loop {
+        match rustls_pemfile::read_one(&mut reader).context("Failed to parse TLS private key")? {
+            Some(rustls_pemfile::Item::PKCS8Key(key))
+            | Some(rustls_pemfile::Item::RSAKey(key))
+            | Some(rustls_pemfile::Item::ECKey(key)) => {
+                return Ok(tokio_rustls::rustls::PrivateKey(key))
+            }
+            Some(_) => continue,
+            None => anyhow::bail!("no private key found in PEM data"),
+        }
+    }
+}
+
+/// Listener for the metrics exporter which serves either plaintext or TLS
+/// connections over a TCP or Unix transport depending on the configuration.
+enum MetricsIncoming {
+    Plain(RawIncoming),
+    Tls(TlsIncoming),
+}
+
+impl Accept for MetricsIncoming {
+    type Conn = MetricsStream;
+    type Error = std::io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<Option<std::io::Result<Self::Conn>>> {
+        match self.get_mut() {
+            MetricsIncoming::Plain(incoming) => incoming
+                .poll_accept(cx)
+                .map(|conn| conn.map(|conn| conn.map(MetricsStream::Plain))),
+            MetricsIncoming::Tls(incoming) => incoming.poll_accept(cx),
+        }
+    }
+}
+
+/// Transport listener producing raw (unencrypted) connections.
+enum RawIncoming {
+    Tcp(AddrIncoming),
+    Unix(UnixIncoming),
+}
+
+impl RawIncoming {
+    fn poll_accept(
+        &mut self,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<Option<std::io::Result<RawStream>>> {
+        match self {
+            RawIncoming::Tcp(incoming) => Pin::new(incoming)
+                .poll_accept(cx)
+                .map(|conn| conn.map(|conn| conn.map(RawStream::Tcp))),
+            RawIncoming::Unix(incoming) => incoming.poll_accept(cx),
+        }
+    }
+}
+
+/// Unix domain socket listener. The socket file is created on [`bind`] and
+/// removed when the listener is dropped (i.e. on shutdown or reload).
+///
+/// [`bind`]: UnixIncoming::bind
+struct UnixIncoming {
+    listener: tokio::net::UnixListener,
+    path: std::path::PathBuf,
+    // Back-off timer armed after a transient accept error so we retry instead of
+    // tearing down the listener (hyper treats a returned error as fatal). Mirrors
+    // the sleep-on-errors behaviour of `AddrIncoming` used by the TCP path.
+    timeout: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl UnixIncoming {
+    fn bind(path: std::path::PathBuf) -> std::io::Result<Self> {
+        // Remove a stale socket left over by a previous run, otherwise `bind`
+        // fails with `EADDRINUSE`.
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(e);
+            }
+        }
+        let listener = tokio::net::UnixListener::bind(&path)?;
+        Ok(Self {
+            listener,
+            path,
+            timeout: None,
+        })
+    }
+
+    fn poll_accept(
+        &mut self,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<Option<std::io::Result<RawStream>>> {
+        use std::future::Future;
+
+        // Wait out a previously armed back-off before accepting again.
+        if let Some(timeout) = &mut self.timeout {
+            futures::ready!(timeout.as_mut().poll(cx));
+            self.timeout = None;
+        }
+
+        loop {
+            match self.listener.poll_accept(cx) {
+                Poll::Ready(Ok((stream, _))) => {
+                    return Poll::Ready(Some(Ok(RawStream::Unix(stream))))
+                }
+                Poll::Ready(Err(e)) if is_connection_error(&e) => {
+                    // The peer went away before we accepted it; just try again.
+                    continue;
+                }
+                Poll::Ready(Err(e)) => {
+                    // A transient resource error (e.g. EMFILE/ENFILE under load).
+                    // Log and back off rather than killing the endpoint.
+                    log::warn!("Metrics exporter unix accept error: {e}");
+                    let mut timeout = Box::pin(tokio::time::sleep(Duration::from_secs(1)));
+                    if timeout.as_mut().poll(cx).is_pending() {
+                        self.timeout = Some(timeout);
+                        return Poll::Pending;
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Whether an accept error reflects a connection that died before acceptance
+/// (safe to skip) rather than a listener-level failure.
+fn is_connection_error(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionAborted
+            | std::io::ErrorKind::ConnectionReset
+    )
+}
+
+impl Drop for UnixIncoming {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// TLS listener which accepts raw connections and drives their handshakes
+/// concurrently. A failed handshake is logged and dropped so a single
+/// misbehaving client can't stall the listener.
+struct TlsIncoming {
+    incoming: RawIncoming,
+    acceptor: TlsAcceptor,
+    handshakes: FuturesUnordered<tokio_rustls::Accept<RawStream>>,
+}
+
+impl TlsIncoming {
+    fn new(incoming: RawIncoming, acceptor: TlsAcceptor) -> Self {
+        Self {
+            incoming,
+            acceptor,
+            handshakes: FuturesUnordered::new(),
+        }
+    }
+
+    fn poll_accept(
+        &mut self,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<Option<std::io::Result<MetricsStream>>> {
+        // Accept every ready connection and start its handshake.
+        loop {
+            match self.incoming.poll_accept(cx) {
+                Poll::Ready(Some(Ok(stream))) => self.handshakes.push(self.acceptor.accept(stream)),
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => break,
+            }
+        }
+
+        // Return the first completed handshake, skipping failed ones.
+        loop {
+            match self.handshakes.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(stream))) => {
+                    return Poll::Ready(Some(Ok(MetricsStream::Tls(Box::new(stream)))))
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    log::debug!("Metrics exporter TLS handshake failed: {:?}", e);
+                    continue;
+                }
+                Poll::Ready(None) | Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// A raw (unencrypted) connection accepted by the exporter.
+enum RawStream {
+    Tcp(AddrStream),
+    Unix(tokio::net::UnixStream),
+}
+
+impl RawStream {
+    /// Human-readable description of the remote peer, used for request logging.
+    fn remote_peer(&self) -> String {
+        match self {
+            RawStream::Tcp(s) => s.remote_addr().to_string(),
+            RawStream::Unix(_) => "unix".to_owned(),
+        }
+    }
+}
+
+impl tokio::io::AsyncRead for RawStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            RawStream::Tcp(s) => Pin::new(s).poll_read(cx, buf),
+            RawStream::Unix(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl tokio::io::AsyncWrite for RawStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            RawStream::Tcp(s) => Pin::new(s).poll_write(cx, buf),
+            RawStream::Unix(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            RawStream::Tcp(s) => Pin::new(s).poll_flush(cx),
+            RawStream::Unix(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            RawStream::Tcp(s) => Pin::new(s).poll_shutdown(cx),
+            RawStream::Unix(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
+
+/// A connection accepted by the exporter: a plain transport stream or a TLS
+/// session on top of one.
+enum MetricsStream {
+    Plain(RawStream),
+    Tls(Box<tokio_rustls::server::TlsStream<RawStream>>),
+}
+
+impl MetricsStream {
+    /// Human-readable description of the remote peer, used for request logging.
+    fn remote_peer(&self) -> String {
+        match self {
+            MetricsStream::Plain(s) => s.remote_peer(),
+            MetricsStream::Tls(s) => s.get_ref().0.remote_peer(),
+        }
+    }
+}
+
+impl tokio::io::AsyncRead for MetricsStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MetricsStream::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            MetricsStream::Tls(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl tokio::io::AsyncWrite for MetricsStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            MetricsStream::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            MetricsStream::Tls(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MetricsStream::Plain(s) => Pin::new(s).poll_flush(cx),
+            MetricsStream::Tls(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MetricsStream::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            MetricsStream::Tls(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth_header(value: &str) -> hyper::HeaderMap {
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(hyper::header::AUTHORIZATION, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn constant_time_eq_matches_only_equal_slices() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreu"));
+        assert!(!constant_time_eq(b"secret", b"secre"));
+        assert!(!constant_time_eq(b"", b"x"));
+    }
+
+    #[test]
+    fn bearer_auth_accepts_only_the_configured_token() {
+        let config = AuthConfig {
+            bearer_token: Some("topsecret".to_owned()),
+            ..Default::default()
+        };
+        let auth = PreparedAuth::from_config(&config).unwrap();
+
+        assert!(auth.is_authorized(&auth_header("Bearer topsecret")));
+        assert!(!auth.is_authorized(&auth_header("Bearer wrong")));
+        assert!(!auth.is_authorized(&hyper::HeaderMap::new()));
+    }
+
+    #[test]
+    fn basic_auth_accepts_encoded_credentials() {
+        let config = AuthConfig {
+            basic_username: Some("user".to_owned()),
+            basic_password: Some("pass".to_owned()),
+            ..Default::default()
+        };
+        let auth = PreparedAuth::from_config(&config).unwrap();
+
+        // base64("user:pass") == "dXNlcjpwYXNz"
+        assert!(auth.is_authorized(&auth_header("Basic dXNlcjpwYXNz")));
+        assert!(!auth.is_authorized(&auth_header("Basic bm9wZQ==")));
+    }
+
+    #[test]
+    fn empty_auth_is_a_hard_error_not_an_open_endpoint() {
+        assert!(PreparedAuth::from_config(&AuthConfig::default()).is_err());
+    }
+
+    #[test]
+    fn partial_basic_auth_is_rejected() {
+        let config = AuthConfig {
+            basic_username: Some("user".to_owned()),
+            ..Default::default()
+        };
+        assert!(PreparedAuth::from_config(&config).is_err());
+
+        let config = AuthConfig {
+            basic_password: Some("pass".to_owned()),
+            ..Default::default()
+        };
+        assert!(PreparedAuth::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn push_uri_appends_grouping_labels() {
+        let config = PushConfig {
+            endpoint: "http://gw:9091".to_owned(),
+            job: "pomfrit".to_owned(),
+            instance: None,
+            ..Default::default()
+        };
+        assert_eq!(push_uri(&config), "http://gw:9091/metrics/job/pomfrit");
+
+        let config = PushConfig {
+            instance: Some("node-1".to_owned()),
+            ..config
+        };
+        assert_eq!(
+            push_uri(&config),
+            "http://gw:9091/metrics/job/pomfrit/instance/node-1"
+        );
+    }
+
+    #[test]
+    fn push_uri_trims_trailing_slash_from_endpoint() {
+        let config = PushConfig {
+            endpoint: "http://gw:9091/".to_owned(),
+            job: "pomfrit".to_owned(),
+            instance: None,
+            ..Default::default()
+        };
+        assert_eq!(push_uri(&config), "http://gw:9091/metrics/job/pomfrit");
+    }
+
+    #[test]
+    fn push_uri_percent_encodes_label_values() {
+        let config = PushConfig {
+            endpoint: "http://gw:9091".to_owned(),
+            job: "ingest/worker".to_owned(),
+            instance: Some("host a".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            push_uri(&config),
+            "http://gw:9091/metrics/job/ingest%2Fworker/instance/host%20a"
+        );
+    }
+
+    #[test]
+    fn validate_push_config_rejects_empty_or_invalid() {
+        assert!(validate_push_config(&PushConfig::default()).is_err());
+        assert!(validate_push_config(&PushConfig {
+            endpoint: "http://gw:9091".to_owned(),
+            job: String::new(),
+            ..Default::default()
+        })
+        .is_err());
+        assert!(validate_push_config(&PushConfig {
+            endpoint: "not a uri".to_owned(),
+            job: "pomfrit".to_owned(),
+            ..Default::default()
+        })
+        .is_err());
+        assert!(validate_push_config(&PushConfig {
+            endpoint: "http://gw:9091".to_owned(),
+            job: "pomfrit".to_owned(),
+            ..Default::default()
+        })
+        .is_ok());
+    }
+
+    fn accept_encoding(value: &str) -> hyper::HeaderMap {
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(hyper::header::ACCEPT_ENCODING, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn accepts_gzip_honors_accept_encoding() {
+        assert!(accepts_gzip(&accept_encoding("gzip")));
+        assert!(accepts_gzip(&accept_encoding("deflate, gzip")));
+        assert!(accepts_gzip(&accept_encoding("gzip;q=0.5")));
+        assert!(!accepts_gzip(&accept_encoding("gzip;q=0")));
+        assert!(!accepts_gzip(&accept_encoding("identity")));
+        assert!(!accepts_gzip(&hyper::HeaderMap::new()));
+    }
+
+    #[test]
+    fn gzip_compress_round_trips() {
+        use std::io::Read;
+
+        let payload = "metric_total 42\n".repeat(128);
+        let compressed = gzip_compress(payload.as_bytes());
+        assert!(compressed.len() < payload.len());
+
+        let mut decoder = flate2::read::GzDecoder::new(compressed.as_slice());
+        let mut decompressed = String::new();
+        decoder.read_to_string(&mut decompressed).unwrap();
+        assert_eq!(decompressed, payload);
+    }
+}
@@ -2,31 +2,98 @@ use std::borrow::Borrow;
 use std::fmt::Write;
 
 pub trait DisplayPrometheusExt<'b> {
-    fn begin_metric<'a>(&'a mut self, name: &str) -> PrometheusFormatter<'a, 'b>;
+    fn begin_metric<'a, 'c>(&'a mut self, name: &'c str) -> PrometheusFormatter<'a, 'b, 'c>;
 }
 
 impl<'b> DisplayPrometheusExt<'b> for std::fmt::Formatter<'b> {
-    fn begin_metric<'a>(&'a mut self, name: &str) -> PrometheusFormatter<'a, 'b> {
+    fn begin_metric<'a, 'c>(&'a mut self, name: &'c str) -> PrometheusFormatter<'a, 'b, 'c> {
         PrometheusFormatter::new(self, name)
     }
 }
 
-pub struct PrometheusFormatter<'a, 'b> {
+/// Prometheus metric type, written as the `# TYPE` metadata line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+    Untyped,
+}
+
+impl MetricType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Histogram => "histogram",
+            MetricType::Summary => "summary",
+            MetricType::Untyped => "untyped",
+        }
+    }
+}
+
+pub struct PrometheusFormatter<'a, 'b, 'c> {
     fmt: &'a mut std::fmt::Formatter<'b>,
+    name: &'c str,
     result: std::fmt::Result,
+    wrote_name: bool,
     has_labels: bool,
 }
 
-impl<'a, 'b> PrometheusFormatter<'a, 'b> {
-    pub fn new(fmt: &'a mut std::fmt::Formatter<'b>, name: &str) -> Self {
-        let result = fmt.write_str(name);
+impl<'a, 'b, 'c> PrometheusFormatter<'a, 'b, 'c> {
+    pub fn new(fmt: &'a mut std::fmt::Formatter<'b>, name: &'c str) -> Self {
         Self {
             fmt,
-            result,
+            name,
+            result: Ok(()),
+            wrote_name: false,
             has_labels: false,
         }
     }
 
+    /// Writes a `# HELP <name> <description>` line before the metric samples.
+    ///
+    /// Backslashes and newlines in the description are escaped as required by
+    /// the text exposition format. Must be called before any label or value.
+    ///
+    /// The text format allows only one `# HELP` line per metric name, so the
+    /// caller is responsible for emitting it once — typically on the first
+    /// sample of a metric and not on subsequent label sets of the same name.
+    #[inline]
+    pub fn help<H>(mut self, help: H) -> Self
+    where
+        H: std::fmt::Display,
+    {
+        self.result = self.result.and_then(|_| {
+            self.fmt.write_str("# HELP ")?;
+            self.fmt.write_str(self.name)?;
+            self.fmt.write_char(' ')?;
+            write_escaped_help(self.fmt, &help.to_string())?;
+            self.fmt.write_char('\n')
+        });
+        self
+    }
+
+    /// Writes a `# TYPE <name> <type>` line before the metric samples.
+    ///
+    /// Must be called before any label or value. As with [`help`], only one
+    /// `# TYPE` line per metric name is valid, so the caller must emit it once
+    /// per name rather than on every label set.
+    ///
+    /// [`help`]: PrometheusFormatter::help
+    #[inline]
+    pub fn type_(mut self, ty: MetricType) -> Self {
+        self.result = self.result.and_then(|_| {
+            self.fmt.write_str("# TYPE ")?;
+            self.fmt.write_str(self.name)?;
+            self.fmt.write_char(' ')?;
+            self.fmt.write_str(ty.as_str())?;
+            self.fmt.write_char('\n')
+        });
+        self
+    }
+
     #[inline]
     pub fn label_opt<N, V>(self, name: N, value: impl Borrow<Option<V>>) -> Self
     where
@@ -41,37 +108,32 @@ impl<'a, 'b> PrometheusFormatter<'a, 'b> {
     }
 
     #[inline]
-    pub fn label<N, V>(self, name: N, value: V) -> Self
+    pub fn label<N, V>(mut self, name: N, value: V) -> Self
     where
         N: std::fmt::Display,
         V: std::fmt::Display,
     {
-        let PrometheusFormatter {
-            fmt,
-            result,
-            has_labels,
-        } = self;
-
-        let result = result.and_then(|_| {
-            fmt.write_char(if has_labels { ',' } else { '{' })?;
-            name.fmt(fmt)?;
-            fmt.write_str("=\"")?;
-            value.fmt(fmt)?;
-            fmt.write_char('\"')
+        self.begin_sample();
+
+        self.result = self.result.and_then(|_| {
+            self.fmt.write_char(if self.has_labels { ',' } else { '{' })?;
+            name.fmt(self.fmt)?;
+            self.fmt.write_str("=\"")?;
+            value.fmt(self.fmt)?;
+            self.fmt.write_char('\"')
         });
+        self.has_labels = true;
 
-        Self {
-            fmt,
-            result,
-            has_labels: true,
-        }
+        self
     }
 
     #[inline]
-    pub fn value<T>(self, value: impl std::borrow::Borrow<T>) -> std::fmt::Result
+    pub fn value<T>(mut self, value: impl std::borrow::Borrow<T>) -> std::fmt::Result
     where
         T: num_traits::Num + std::fmt::Display,
     {
+        self.begin_sample();
+
         self.result.and_then(|_| {
             if self.has_labels {
                 self.fmt.write_str("} ")?;
@@ -82,4 +144,54 @@ impl<'a, 'b> PrometheusFormatter<'a, 'b> {
             self.fmt.write_char('\n')
         })
     }
+
+    /// Writes the metric name once, right before the first label or value so
+    /// that any `# HELP`/`# TYPE` lines precede it.
+    #[inline]
+    fn begin_sample(&mut self) {
+        if !self.wrote_name {
+            self.wrote_name = true;
+            let name = self.name;
+            self.result = self.result.and_then(|_| self.fmt.write_str(name));
+        }
+    }
+}
+
+/// Escapes backslash and newline in `# HELP` descriptions.
+fn write_escaped_help(fmt: &mut std::fmt::Formatter<'_>, help: &str) -> std::fmt::Result {
+    for ch in help.chars() {
+        match ch {
+            '\\' => fmt.write_str("\\\\")?,
+            '\n' => fmt.write_str("\\n")?,
+            c => fmt.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Described;
+
+    impl std::fmt::Display for Described {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.begin_metric("requests_total")
+                .help("total requests\\handled\nper second")
+                .type_(MetricType::Counter)
+                .label("method", "get")
+                .value(42)
+        }
+    }
+
+    #[test]
+    fn writes_help_and_type_before_samples() {
+        assert_eq!(
+            Described.to_string(),
+            "# HELP requests_total total requests\\\\handled\\nper second\n\
+             # TYPE requests_total counter\n\
+             requests_total{method=\"get\"} 42\n"
+        );
+    }
 }